@@ -13,13 +13,17 @@
 // limitations under the License.
 
 use std::clone::Clone;
+use std::collections::HashMap;
+use std::fmt;
 use std::iter::Iterator;
 use std::rc::Rc;
 use std::str;
 use std::string::String;
 use std::cmp::{Ordering, PartialOrd};
 
-#[derive(Debug,Clone,Serialize,Deserialize)]
+use rhai::{Engine, AST, Dynamic, EvalAltResult, Scope};
+
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
 pub enum DataType {
     Boolean,
     Float32,
@@ -30,7 +34,32 @@ pub enum DataType {
     Struct(Vec<Field>)
 }
 
-#[derive(Debug,Clone,Serialize,Deserialize)]
+/// The common type `a` and `b` should both be promoted to before an elementwise comparison,
+/// following the numeric lattice `Int32 ⊂ Int64 ⊂ Float32 ⊂ Float64`. `Boolean` and `Utf8` are
+/// only comparable to themselves; anything else (e.g. `Struct`) has no common type.
+pub fn coerce(a: &DataType, b: &DataType) -> Option<DataType> {
+    if a == b {
+        return match a {
+            &DataType::Struct(_) => None,
+            _ => Some(a.clone()),
+        };
+    }
+    fn numeric_rank(dt: &DataType) -> Option<u8> {
+        match dt {
+            &DataType::Int32 => Some(0),
+            &DataType::Int64 => Some(1),
+            &DataType::Float32 => Some(2),
+            &DataType::Float64 => Some(3),
+            _ => None,
+        }
+    }
+    match (numeric_rank(a), numeric_rank(b)) {
+        (Some(ra), Some(rb)) => Some(if ra >= rb { a.clone() } else { b.clone() }),
+        _ => None,
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
 pub struct Field {
     pub name: String,
     pub data_type: DataType,
@@ -79,177 +108,642 @@ impl Schema {
 
 }
 
+/// A packed validity bitmap for a nullable `Array`, one bit per value (set bit = valid /
+/// non-null), following the Arrow columnar format rather than a `Vec<bool>` per value.
+#[derive(Debug,Clone)]
+pub struct Bitmap {
+    bits: Vec<u8>,
+    len: usize,
+    null_count: usize,
+}
 
-#[derive(Debug)]
+impl Bitmap {
+
+    /// A bitmap of `len` bits with every value marked valid.
+    pub fn all_valid(len: usize) -> Self {
+        Bitmap { bits: vec![0xffu8; (len + 7) / 8], len: len, null_count: 0 }
+    }
+
+    /// Build a bitmap from a flag per value (`true` means valid).
+    pub fn from_flags(valid: &[bool]) -> Self {
+        let mut bits = vec![0u8; (valid.len() + 7) / 8];
+        let mut null_count = 0;
+        for (i, v) in valid.iter().enumerate() {
+            if *v {
+                bits[i / 8] |= 1 << (i % 8);
+            } else {
+                null_count += 1;
+            }
+        }
+        Bitmap { bits: bits, len: valid.len(), null_count: null_count }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_valid(&self, index: usize) -> bool {
+        (self.bits[index / 8] >> (index % 8)) & 1 == 1
+    }
+
+    pub fn is_null(&self, index: usize) -> bool {
+        !self.is_valid(index)
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+}
+
+#[derive(Debug,Clone)]
 pub enum Array {
     BroadcastVariable(Value), //TODO remove .. not an arrow concept
-    Boolean(Vec<bool>),
-    Float32(Vec<f32>),
-    Float64(Vec<f64>),
-    Int32(Vec<i32>),
-    Int64(Vec<i64>),
-    Utf8(Vec<String>),
-    Struct(Vec<Rc<Array>>)
+    Boolean(Vec<bool>, Bitmap),
+    Float32(Vec<f32>, Bitmap),
+    Float64(Vec<f64>, Bitmap),
+    Int32(Vec<i32>, Bitmap),
+    Int64(Vec<i64>, Bitmap),
+    Utf8(Vec<String>, Bitmap),
+    Struct(Vec<Rc<Array>>, Bitmap),
+    /// Dictionary-encoded strings: `values` holds each unique string once, `keys` indexes into
+    /// it one entry per row (a negative key means the row is null). Built with
+    /// `encode_dictionary()` from a plain `Utf8` array.
+    Dictionary { keys: Vec<i32>, values: Rc<Array> }
+}
+
+/// Build a dense array of `target`'s type from one `Option<Value>` per row (`None` is null).
+/// Used by `Array::cast_to` once a common type has already been resolved.
+fn build_array(target: &DataType, values: Vec<Option<Value>>) -> Array {
+    let valid: Vec<bool> = values.iter().map(|v| v.is_some()).collect();
+    let bitmap = Bitmap::from_flags(&valid);
+    match target {
+        &DataType::Boolean => Array::Boolean(
+            values.into_iter().map(|v| if let Some(Value::Boolean(b)) = v { b } else { bool::default() }).collect(), bitmap),
+        &DataType::Float32 => Array::Float32(
+            values.into_iter().map(|v| if let Some(Value::Float32(f)) = v { f } else { f32::default() }).collect(), bitmap),
+        &DataType::Float64 => Array::Float64(
+            values.into_iter().map(|v| if let Some(Value::Float64(f)) = v { f } else { f64::default() }).collect(), bitmap),
+        &DataType::Int32 => Array::Int32(
+            values.into_iter().map(|v| if let Some(Value::Int32(i)) = v { i } else { i32::default() }).collect(), bitmap),
+        &DataType::Int64 => Array::Int64(
+            values.into_iter().map(|v| if let Some(Value::Int64(i)) = v { i } else { i64::default() }).collect(), bitmap),
+        &DataType::Utf8 => Array::Utf8(
+            values.into_iter().map(|v| if let Some(Value::Utf8(s)) = v { s } else { String::default() }).collect(), bitmap),
+        &DataType::Struct(_) => panic!("build_array() does not support Struct"),
+    }
+}
+
+/// Applies a numeric binary operator across every same-typed combination of column/column and
+/// column/literal, propagating validity with `combine_validity`, and panics with `$err_name` on
+/// a type mismatch. Used by `Array::add`/`subtract`/`multiply`/`divide`/`modulus` to avoid five
+/// near-identical nine-arm match blocks.
+macro_rules! numeric_binary_kernel {
+    ($self_:expr, $other:expr, $op:tt, $err_name:expr) => {{
+        let validity = $self_.combine_validity($other);
+        match ($self_, $other) {
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) =>
+                Array::Float32(l.iter().zip(r.iter()).map(|(a,b)| a $op b).collect(), validity),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) =>
+                Array::Float32(l.iter().map(|a| a $op b).collect(), validity),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) =>
+                Array::Float64(l.iter().zip(r.iter()).map(|(a,b)| a $op b).collect(), validity),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) =>
+                Array::Float64(l.iter().map(|a| a $op b).collect(), validity),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) =>
+                Array::Int32(l.iter().zip(r.iter()).map(|(a,b)| a $op b).collect(), validity),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) =>
+                Array::Int32(l.iter().map(|a| a $op b).collect(), validity),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) =>
+                Array::Int64(l.iter().zip(r.iter()).map(|(a,b)| a $op b).collect(), validity),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) =>
+                Array::Int64(l.iter().map(|a| a $op b).collect(), validity),
+            _ => panic!(format!("{} Type mismatch: {:?} vs {:?}", $err_name, $self_, $other))
+        }
+    }};
 }
 
 impl Array {
 
+    /// Wrap a dense, non-nullable `Vec<bool>` with an all-valid bitmap.
+    pub fn from_bool(v: Vec<bool>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Boolean(v, bitmap)
+    }
+
+    /// Wrap a dense, non-nullable `Vec<f32>` with an all-valid bitmap.
+    pub fn from_f32(v: Vec<f32>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Float32(v, bitmap)
+    }
+
+    /// Wrap a dense, non-nullable `Vec<f64>` with an all-valid bitmap.
+    pub fn from_f64(v: Vec<f64>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Float64(v, bitmap)
+    }
+
+    /// Wrap a dense, non-nullable `Vec<i32>` with an all-valid bitmap.
+    pub fn from_i32(v: Vec<i32>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Int32(v, bitmap)
+    }
+
+    /// Wrap a dense, non-nullable `Vec<i64>` with an all-valid bitmap.
+    pub fn from_i64(v: Vec<i64>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Int64(v, bitmap)
+    }
+
+    /// Wrap a dense, non-nullable `Vec<String>` with an all-valid bitmap.
+    pub fn from_utf8(v: Vec<String>) -> Self {
+        let bitmap = Bitmap::all_valid(v.len());
+        Array::Utf8(v, bitmap)
+    }
+
+    /// The logical `DataType` this array holds. A `BroadcastVariable` reports its scalar's type
+    /// and a `Dictionary` reports its `values`' type.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            &Array::BroadcastVariable(ref v) => v.data_type(),
+            &Array::Boolean(..) => DataType::Boolean,
+            &Array::Float32(..) => DataType::Float32,
+            &Array::Float64(..) => DataType::Float64,
+            &Array::Int32(..) => DataType::Int32,
+            &Array::Int64(..) => DataType::Int64,
+            &Array::Utf8(..) => DataType::Utf8,
+            &Array::Struct(..) => DataType::Struct(vec![]),
+            &Array::Dictionary { ref values, .. } => values.data_type(),
+        }
+    }
+
+    /// Promote every element to `target`'s representation, preserving validity. Only meant to be
+    /// called with a `target` that `coerce` has already confirmed is reachable from this array's
+    /// own type.
+    fn cast_to(&self, target: &DataType) -> Array {
+        match self {
+            &Array::BroadcastVariable(ref v) => Array::BroadcastVariable(
+                v.cast(target).expect("cast() should succeed for a coerced common type")
+            ),
+            _ => {
+                let values: Vec<Option<Value>> = (0..self.len()).map(|i| match self.get_value(i) {
+                    None => None,
+                    Some(v) => Some(v.cast(target).expect("cast() should succeed for a coerced common type")),
+                }).collect();
+                build_array(target, values)
+            }
+        }
+    }
+
     pub fn len(&self) -> usize {
         match self {
             &Array::BroadcastVariable(_) => 1,
-            &Array::Boolean(ref v) => v.len(),
-            &Array::Float32(ref v) => v.len(),
-            &Array::Float64(ref v) => v.len(),
-            &Array::Int32(ref v) => v.len(),
-            &Array::Int64(ref v) => v.len(),
-            &Array::Utf8(ref v) => v.len(),
-            &Array::Struct(ref v) => v[0].len(),
+            &Array::Boolean(ref v, _) => v.len(),
+            &Array::Float32(ref v, _) => v.len(),
+            &Array::Float64(ref v, _) => v.len(),
+            &Array::Int32(ref v, _) => v.len(),
+            &Array::Int64(ref v, _) => v.len(),
+            &Array::Utf8(ref v, _) => v.len(),
+            &Array::Struct(ref v, _) => v[0].len(),
+            &Array::Dictionary { ref keys, .. } => keys.len(),
         }
     }
 
-    pub fn eq(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// Number of null (invalid) slots. A `BroadcastVariable` is always valid.
+    pub fn null_count(&self) -> usize {
+        match self {
+            &Array::BroadcastVariable(_) => 0,
+            &Array::Boolean(_, ref bitmap) => bitmap.null_count(),
+            &Array::Float32(_, ref bitmap) => bitmap.null_count(),
+            &Array::Float64(_, ref bitmap) => bitmap.null_count(),
+            &Array::Int32(_, ref bitmap) => bitmap.null_count(),
+            &Array::Int64(_, ref bitmap) => bitmap.null_count(),
+            &Array::Utf8(_, ref bitmap) => bitmap.null_count(),
+            &Array::Struct(_, ref bitmap) => bitmap.null_count(),
+            &Array::Dictionary { .. } => (0..self.len()).filter(|&i| !self.is_valid(i)).count(),
+        }
+    }
+
+    /// Whether the value at `index` is non-null. A `BroadcastVariable` is always valid. A
+    /// `Dictionary` entry is null if its key is negative or the value it points to is null.
+    pub fn is_valid(&self, index: usize) -> bool {
+        match self {
+            &Array::BroadcastVariable(_) => true,
+            &Array::Boolean(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Float32(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Float64(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Int32(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Int64(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Utf8(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Struct(_, ref bitmap) => bitmap.is_valid(index),
+            &Array::Dictionary { ref keys, ref values } =>
+                keys[index] >= 0 && values.is_valid(keys[index] as usize),
+        }
+    }
+
+    /// The validity a binary kernel's result should carry: valid only where both operands are
+    /// (three-valued logic, i.e. `null op anything = null`). A `BroadcastVariable` operand is
+    /// valid for every row it's compared against, rather than only at index 0.
+    fn combine_validity(&self, other: &Array) -> Bitmap {
+        let len = match self {
+            &Array::BroadcastVariable(_) => other.len(),
+            _ => self.len(),
+        };
+        let flags: Vec<bool> = (0..len).map(|i| {
+            let l = match self {
+                &Array::BroadcastVariable(_) => true,
+                _ => self.is_valid(i),
+            };
+            let r = match other {
+                &Array::BroadcastVariable(_) => true,
+                _ => other.is_valid(i),
+            };
+            l && r
+        }).collect();
+        Bitmap::from_flags(&flags)
+    }
+
+    /// Compares two operands element-wise. When their types differ, both are promoted to a
+    /// common type via `coerce` first (e.g. an `Int32` column against an `Int64` literal); a
+    /// pairing with no common type reports a typed error instead of panicking.
+    pub fn eq(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a==&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a==&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a==&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a==&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a==b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a==&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a==&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a==&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a==&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a==b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a==b],
+            // dictionary fast path: compare the small `values` array to the literal once, then
+            // map each row's key through the result instead of one string comparison per row
+            (&Array::Dictionary { ref keys, ref values }, &Array::BroadcastVariable(Value::Utf8(_))) => {
+                match values.eq(other)? {
+                    Array::Boolean(ref vm, _) => keys.iter().map(|&k| k >= 0 && vm[k as usize]).collect(),
+                    _ => unreachable!()
+                }
+            },
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
-            _ => panic!(format!("ColumnData.eq() Type mismatch: {:?} vs {:?}", self, other))
-        }
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a==b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.eq() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).eq(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
     }
 
-    pub fn not_eq(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// See `eq` for the cross-type coercion behavior.
+    pub fn not_eq(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a!=&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a!=&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a!=&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a!=&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a!=b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a!=&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a!=&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a!=&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a!=&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a!=b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a!=b],
+            // dictionary fast path: see the matching arm in `eq`
+            (&Array::Dictionary { ref keys, ref values }, &Array::BroadcastVariable(Value::Utf8(_))) => {
+                match values.eq(other)? {
+                    Array::Boolean(ref vm, _) => keys.iter().map(|&k| k < 0 || !vm[k as usize]).collect(),
+                    _ => unreachable!()
+                }
+            },
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
-            _ => panic!(format!("ColumnData.eq() Type mismatch: {:?} vs {:?}", self, other))
-        }
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a!=b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.not_eq() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).not_eq(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
     }
 
-    pub fn lt(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// See `eq` for the cross-type coercion behavior.
+    pub fn lt(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a<&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a<&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a<&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a<&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a<b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a<&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a<&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a<&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a<&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a<b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a<b],
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
-            _ => panic!(format!("ColumnData.lt() Type mismatch: {:?} vs {:?}", self, other))
-        }
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.lt() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).lt(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
     }
 
-    pub fn lt_eq(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// See `eq` for the cross-type coercion behavior.
+    pub fn lt_eq(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a<=&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a<=&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a<=&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a<=&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a<=b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a<=&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a<=&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a<=&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a<=&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a<=b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a<=b],
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
-            _ => panic!(format!("ColumnData.lt_eq() Type mismatch: {:?} vs {:?}", self, other))
-        }
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a<=b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.lt_eq() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).lt_eq(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
     }
 
-    pub fn gt(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// See `eq` for the cross-type coercion behavior.
+    pub fn gt(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a>&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a>&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a>&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a>&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a>b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a>&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a>&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a>&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a>&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a>b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a>b],
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
-            _ => panic!(format!("ColumnData.gt() Type mismatch: {:?} vs {:?}", self, other))
-        }
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.gt() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).gt(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
     }
 
-    pub fn gt_eq(&self, other: &Array) -> Vec<bool> {
-        match (self, other) {
+    /// See `eq` for the cross-type coercion behavior.
+    pub fn gt_eq(&self, other: &Array) -> Result<Array, String> {
+        let validity = self.combine_validity(other);
+        let values = match (self, other) {
             // compare column to literal
-            (&Array::Float32(ref l), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a>=&b).collect(),
-            (&Array::Float64(ref l), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a>=&b).collect(),
-            (&Array::Int32(ref l), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a>=&b).collect(),
-            (&Array::Int64(ref l), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a>=&b).collect(),
-            (&Array::Utf8(ref l), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a>=b).collect(),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) => l.iter().map(|a| a>=&b).collect(),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) => l.iter().map(|a| a>=&b).collect(),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) => l.iter().map(|a| a>=&b).collect(),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) => l.iter().map(|a| a>=&b).collect(),
+            (&Array::Utf8(ref l, _), &Array::BroadcastVariable(Value::Utf8(ref b))) => l.iter().map(|a| a>=b).collect(),
+            (&Array::BroadcastVariable(ref a), &Array::BroadcastVariable(ref b)) => vec![a>=b],
             // compare column to column
-            (&Array::Float32(ref l), &Array::Float32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
-            (&Array::Float64(ref l), &Array::Float64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
-            (&Array::Int32(ref l), &Array::Int32(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
-            (&Array::Int64(ref l), &Array::Int64(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
-            (&Array::Utf8(ref l), &Array::Utf8(ref r)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
-            _ => panic!(format!("ColumnData.gt_eq() Type mismatch: {:?} vs {:?}", self, other))
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
+            (&Array::Utf8(ref l, _), &Array::Utf8(ref r, _)) => l.iter().zip(r.iter()).map(|(a,b)| a>=b).collect(),
+            // cross-type: promote both operands to their common type, then retry
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())
+                    .ok_or_else(|| format!("Array.gt_eq() no common type for {:?} vs {:?}", self.data_type(), other.data_type()))?;
+                return self.cast_to(&common).gt_eq(&other.cast_to(&common));
+            }
+        };
+        Ok(Array::Boolean(values, validity))
+    }
+
+    /// Kleene (three-valued) AND: a valid `false` operand forces the result to `false` even if
+    /// the other operand is null, otherwise the result is null unless both operands are valid.
+    pub fn and(&self, other: &Array) -> Array {
+        match (self, other) {
+            (&Array::Boolean(ref l, ref lbm), &Array::Boolean(ref r, ref rbm)) => {
+                let values: Vec<bool> = (0..l.len()).map(|i| l[i] && r[i]).collect();
+                let valid: Vec<bool> = (0..l.len()).map(|i| {
+                    let l_false = lbm.is_valid(i) && !l[i];
+                    let r_false = rbm.is_valid(i) && !r[i];
+                    (lbm.is_valid(i) && rbm.is_valid(i)) || l_false || r_false
+                }).collect();
+                Array::Boolean(values, Bitmap::from_flags(&valid))
+            },
+            _ => panic!(format!("Array.and() Type mismatch: {:?} vs {:?}", self, other))
+        }
+    }
+
+    /// Kleene (three-valued) OR: a valid `true` operand forces the result to `true` even if the
+    /// other operand is null, otherwise the result is null unless both operands are valid.
+    pub fn or(&self, other: &Array) -> Array {
+        match (self, other) {
+            (&Array::Boolean(ref l, ref lbm), &Array::Boolean(ref r, ref rbm)) => {
+                let values: Vec<bool> = (0..l.len()).map(|i| l[i] || r[i]).collect();
+                let valid: Vec<bool> = (0..l.len()).map(|i| {
+                    let l_true = lbm.is_valid(i) && l[i];
+                    let r_true = rbm.is_valid(i) && r[i];
+                    (lbm.is_valid(i) && rbm.is_valid(i)) || l_true || r_true
+                }).collect();
+                Array::Boolean(values, Bitmap::from_flags(&valid))
+            },
+            _ => panic!(format!("Array.or() Type mismatch: {:?} vs {:?}", self, other))
         }
     }
 
-    pub fn get_value(&self, index: usize) -> Value {
-//        println!("get_value() index={}", index);
+    pub fn add(&self, other: &Array) -> Array {
+        numeric_binary_kernel!(self, other, +, "Array.add()")
+    }
+
+    pub fn subtract(&self, other: &Array) -> Array {
+        numeric_binary_kernel!(self, other, -, "Array.subtract()")
+    }
+
+    pub fn multiply(&self, other: &Array) -> Array {
+        numeric_binary_kernel!(self, other, *, "Array.multiply()")
+    }
+
+    pub fn divide(&self, other: &Array) -> Array {
+        numeric_binary_kernel!(self, other, /, "Array.divide()")
+    }
+
+    pub fn modulus(&self, other: &Array) -> Array {
+        numeric_binary_kernel!(self, other, %, "Array.modulus()")
+    }
+
+    /// Raises each element of `self` to the power of the matching element of `other`. Not part of
+    /// `numeric_binary_kernel!` since `^` isn't a native Rust binary operator on these types.
+    pub fn power(&self, other: &Array) -> Array {
+        let validity = self.combine_validity(other);
+        match (self, other) {
+            (&Array::Float32(ref l, _), &Array::Float32(ref r, _)) =>
+                Array::Float32(l.iter().zip(r.iter()).map(|(a,b)| a.powf(*b)).collect(), validity),
+            (&Array::Float32(ref l, _), &Array::BroadcastVariable(Value::Float32(b))) =>
+                Array::Float32(l.iter().map(|a| a.powf(b)).collect(), validity),
+            (&Array::Float64(ref l, _), &Array::Float64(ref r, _)) =>
+                Array::Float64(l.iter().zip(r.iter()).map(|(a,b)| a.powf(*b)).collect(), validity),
+            (&Array::Float64(ref l, _), &Array::BroadcastVariable(Value::Float64(b))) =>
+                Array::Float64(l.iter().map(|a| a.powf(b)).collect(), validity),
+            (&Array::Int32(ref l, _), &Array::Int32(ref r, _)) =>
+                Array::Int32(l.iter().zip(r.iter()).map(|(a,b)| a.pow(*b as u32)).collect(), validity),
+            (&Array::Int32(ref l, _), &Array::BroadcastVariable(Value::Int32(b))) =>
+                Array::Int32(l.iter().map(|a| a.pow(b as u32)).collect(), validity),
+            (&Array::Int64(ref l, _), &Array::Int64(ref r, _)) =>
+                Array::Int64(l.iter().zip(r.iter()).map(|(a,b)| a.pow(*b as u32)).collect(), validity),
+            (&Array::Int64(ref l, _), &Array::BroadcastVariable(Value::Int64(b))) =>
+                Array::Int64(l.iter().map(|a| a.pow(b as u32)).collect(), validity),
+            _ => panic!(format!("Array.power() Type mismatch: {:?} vs {:?}", self, other))
+        }
+    }
+
+    /// Returns `None` when the slot is null, `Some(value)` otherwise.
+    pub fn get_value(&self, index: usize) -> Option<Value> {
+        if !self.is_valid(index) {
+            return None;
+        }
         let v = match self {
             &Array::BroadcastVariable(ref v) => v.clone(),
-            &Array::Boolean(ref v) => Value::Boolean(v[index]),
-            &Array::Float32(ref v) => Value::Float32(v[index]),
-            &Array::Float64(ref v) => Value::Float64(v[index]),
-            &Array::Int32(ref v) => Value::Int32(v[index]),
-            &Array::Int64(ref v) => Value::Int64(v[index]),
-            &Array::Utf8(ref v) => Value::Utf8(v[index].clone()),
-            &Array::Struct(ref v) => {
-                // v is Vec<ColumnData>
-                // each field has its own ColumnData e.g. lat, lon so we want to get a value from each (but it's recursive)
-                //            println!("get_value() complex value has {} fields", v.len());
-                let fields = v.iter().map(|field| field.get_value(index)).collect();
-                Value::Struct(fields)
+            &Array::Boolean(ref v, _) => Value::Boolean(v[index]),
+            &Array::Float32(ref v, _) => Value::Float32(v[index]),
+            &Array::Float64(ref v, _) => Value::Float64(v[index]),
+            &Array::Int32(ref v, _) => Value::Int32(v[index]),
+            &Array::Int64(ref v, _) => Value::Int64(v[index]),
+            &Array::Utf8(ref v, _) => Value::Utf8(v[index].clone()),
+            &Array::Struct(ref v, _) => {
+                // v is Vec<Array>, one per field; a struct row is only present if every field is
+                match v.iter().map(|field| field.get_value(index)).collect::<Option<Vec<Value>>>() {
+                    Some(fields) => Value::Struct(fields),
+                    None => return None,
+                }
+            },
+            &Array::Dictionary { ref keys, ref values } => {
+                // already known valid (and thus keys[index] >= 0) by the is_valid() check above
+                match values.get_value(keys[index] as usize) {
+                    Some(v) => v,
+                    None => return None,
+                }
             }
         };
-        //  println!("get_value() index={} returned {:?}", index, v);
 
-        v
+        Some(v)
+    }
+
+    /// Build a dictionary-encoded copy of a `Utf8` array: `values` holds each unique string once
+    /// and `keys` indexes into it, so equality scans over low-cardinality columns can compare
+    /// against `values` instead of every row.
+    pub fn encode_dictionary(&self) -> Array {
+        match self {
+            &Array::Utf8(ref v, ref bm) => {
+                let mut values: Vec<String> = vec![];
+                let mut index: HashMap<String, i32> = HashMap::new();
+                let keys: Vec<i32> = (0..v.len()).map(|i| {
+                    if !bm.is_valid(i) {
+                        return -1;
+                    }
+                    match index.get(&v[i]) {
+                        Some(&k) => k,
+                        None => {
+                            let k = values.len() as i32;
+                            values.push(v[i].clone());
+                            index.insert(v[i].clone(), k);
+                            k
+                        }
+                    }
+                }).collect();
+                Array::Dictionary { keys: keys, values: Rc::new(Array::from_utf8(values)) }
+            },
+            _ => panic!("encode_dictionary() is only supported for Utf8 arrays")
+        }
     }
 
+    /// Materialize a `Dictionary` array back into a plain `Utf8` array.
+    pub fn decode_dictionary(&self) -> Array {
+        match self {
+            &Array::Dictionary { ref keys, ref values } => {
+                match values.as_ref() {
+                    &Array::Utf8(ref v, ref vbm) => {
+                        let strings: Vec<String> = keys.iter()
+                            .map(|&k| if k >= 0 { v[k as usize].clone() } else { String::new() })
+                            .collect();
+                        let valid: Vec<bool> = keys.iter().map(|&k| k >= 0 && vbm.is_valid(k as usize)).collect();
+                        Array::Utf8(strings, Bitmap::from_flags(&valid))
+                    },
+                    _ => panic!("decode_dictionary() only supports Utf8-backed dictionaries")
+                }
+            },
+            _ => panic!("decode_dictionary() is only supported for Dictionary arrays")
+        }
+    }
+
+    /// Keep only the rows where `bools` is `true`; a null predicate entry drops the row, the
+    /// same as `false`. The kept rows' own validity is carried over into the result.
     pub fn filter(&self, bools: &Array) -> Array {
         match bools {
-            &Array::Boolean(ref b) => match self {
-                &Array::Boolean(ref v) => Array::Boolean(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| *v).collect()),
-                &Array::Float32(ref v) => Array::Float32(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| *v).collect()),
-                &Array::Float64(ref v) => Array::Float64(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| *v).collect()),
-                &Array::Int32(ref v) => Array::Int32(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| *v).collect()),
-                &Array::Int64(ref v) => Array::Int64(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| *v).collect()),
-                &Array::Utf8(ref v) => Array::Utf8(v.iter().zip(b.iter()).filter(|&(_,f)| *f).map(|(v,_)| v.clone()).collect()),
-                _ => unimplemented!()
+            &Array::Boolean(ref b, ref bm) => {
+                let keep: Vec<bool> = (0..b.len()).map(|i| bm.is_valid(i) && b[i]).collect();
+                match self {
+                    &Array::Boolean(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| *v).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Boolean(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Float32(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| *v).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Float32(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Float64(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| *v).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Float64(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Int32(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| *v).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Int32(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Int64(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| *v).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Int64(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Utf8(ref v, ref vbm) => {
+                        let values = v.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(v,_)| v.clone()).collect();
+                        let valid: Vec<bool> = (0..v.len()).filter(|&i| keep[i]).map(|i| vbm.is_valid(i)).collect();
+                        Array::Utf8(values, Bitmap::from_flags(&valid))
+                    },
+                    &Array::Dictionary { ref keys, ref values } => {
+                        let new_keys = keys.iter().zip(keep.iter()).filter(|&(_,k)| *k).map(|(k,_)| *k).collect();
+                        Array::Dictionary { keys: new_keys, values: values.clone() }
+                    },
+                    _ => unimplemented!()
+                }
             },
-            _ => panic!()
+            _ => panic!("filter mask must be a Boolean array")
         }
     }
 
@@ -270,28 +764,23 @@ pub enum Value {
 
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
-
-        //TODO: implement all type coercion rules
-
-        match self {
-            &Value::Float64(l) => match other {
-                &Value::Float64(r) => l.partial_cmp(&r),
-                &Value::Int64(r) => l.partial_cmp(&(r as f64)),
-                _ => unimplemented!("type coercion rules missing")
-            },
-            &Value::Int64(l) => match other {
-                &Value::Float64(r) => (l as f64).partial_cmp(&r),
-                &Value::Int64(r) => l.partial_cmp(&r),
-                _ => unimplemented!("type coercion rules missing")
-            },
-            &Value::Utf8(ref l) => match other {
-                &Value::Utf8(ref r) => l.partial_cmp(r),
-                _ => unimplemented!("type coercion rules missing")
-            },
-            &Value::Struct(_) => None,
-            _ => unimplemented!("type coercion rules missing")
+        match (self, other) {
+            (&Value::Struct(_), _) | (_, &Value::Struct(_)) => None,
+            _ => {
+                let common = coerce(&self.data_type(), &other.data_type())?;
+                let l = self.cast(&common)?;
+                let r = other.cast(&common)?;
+                match (l, r) {
+                    (Value::Boolean(a), Value::Boolean(b)) => a.partial_cmp(&b),
+                    (Value::Int32(a), Value::Int32(b)) => a.partial_cmp(&b),
+                    (Value::Int64(a), Value::Int64(b)) => a.partial_cmp(&b),
+                    (Value::Float32(a), Value::Float32(b)) => a.partial_cmp(&b),
+                    (Value::Float64(a), Value::Float64(b)) => a.partial_cmp(&b),
+                    (Value::Utf8(a), Value::Utf8(b)) => a.partial_cmp(&b),
+                    _ => None,
+                }
+            }
         }
-
     }
 }
 
@@ -315,4 +804,510 @@ impl Value {
         }
     }
 
+    /// The `DataType` this value represents. A `Struct` reports an empty field list since a bare
+    /// `Value` doesn't carry field names; it is never used for coercion (see `partial_cmp`).
+    pub fn data_type(&self) -> DataType {
+        match self {
+            &Value::Boolean(_) => DataType::Boolean,
+            &Value::Float32(_) => DataType::Float32,
+            &Value::Float64(_) => DataType::Float64,
+            &Value::Int32(_) => DataType::Int32,
+            &Value::Int64(_) => DataType::Int64,
+            &Value::Utf8(_) => DataType::Utf8,
+            &Value::Struct(_) => DataType::Struct(vec![]),
+        }
+    }
+
+    /// Attempt to convert this value into `target`'s representation, following the same
+    /// `Int32 ⊂ Int64 ⊂ Float32 ⊂ Float64` lattice as `coerce`. `None` if the types are
+    /// fundamentally incompatible (e.g. casting a `Utf8` to `Int64`).
+    pub fn cast(&self, target: &DataType) -> Option<Value> {
+        match (self, target) {
+            (&Value::Boolean(b), &DataType::Boolean) => Some(Value::Boolean(b)),
+            (&Value::Utf8(ref s), &DataType::Utf8) => Some(Value::Utf8(s.clone())),
+            (&Value::Struct(ref v), &DataType::Struct(_)) => Some(Value::Struct(v.clone())),
+            (&Value::Int32(i), &DataType::Int32) => Some(Value::Int32(i)),
+            (&Value::Int32(i), &DataType::Int64) => Some(Value::Int64(i as i64)),
+            (&Value::Int32(i), &DataType::Float32) => Some(Value::Float32(i as f32)),
+            (&Value::Int32(i), &DataType::Float64) => Some(Value::Float64(i as f64)),
+            (&Value::Int64(i), &DataType::Int64) => Some(Value::Int64(i)),
+            (&Value::Int64(i), &DataType::Float32) => Some(Value::Float32(i as f32)),
+            (&Value::Int64(i), &DataType::Float64) => Some(Value::Float64(i as f64)),
+            (&Value::Float32(f), &DataType::Float32) => Some(Value::Float32(f)),
+            (&Value::Float32(f), &DataType::Float64) => Some(Value::Float64(f as f64)),
+            (&Value::Float64(f), &DataType::Float64) => Some(Value::Float64(f)),
+            _ => None,
+        }
+    }
+
+}
+
+/// A binary operator in an `Expr::BinaryExpr` node.
+#[derive(Debug,Clone,PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulus,
+    Power,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+}
+
+/// Error produced while compiling or evaluating a `ScalarUdf`.
+#[derive(Debug)]
+pub enum UdfError {
+    /// The Rhai source failed to parse.
+    Compile(String),
+    /// The script referenced a column name that wasn't bound into its scope.
+    UnknownColumn(String),
+    /// Evaluating the compiled script raised a Rhai runtime error.
+    Eval(String),
+    /// The script's result couldn't be converted back into a `Value`, or rows disagreed on type.
+    UnsupportedReturnType(String),
+}
+
+impl Value {
+
+    /// Convert into a Rhai `Dynamic` for binding as a script variable.
+    fn to_dynamic(&self) -> Dynamic {
+        match self {
+            &Value::Boolean(b) => Dynamic::from(b),
+            &Value::Int32(i) => Dynamic::from(i as i64),
+            &Value::Int64(i) => Dynamic::from(i),
+            &Value::Float32(f) => Dynamic::from(f as f64),
+            &Value::Float64(f) => Dynamic::from(f),
+            &Value::Utf8(ref s) => Dynamic::from(s.clone()),
+            &Value::Struct(_) => Dynamic::from(()),
+        }
+    }
+
+    /// Convert a Rhai script's `Dynamic` result back into a `Value`.
+    fn from_dynamic(d: &Dynamic) -> Result<Value, UdfError> {
+        if let Some(b) = d.clone().try_cast::<bool>() {
+            Ok(Value::Boolean(b))
+        } else if let Some(i) = d.clone().try_cast::<i64>() {
+            Ok(Value::Int64(i))
+        } else if let Some(f) = d.clone().try_cast::<f64>() {
+            Ok(Value::Float64(f))
+        } else if let Some(s) = d.clone().try_cast::<String>() {
+            Ok(Value::Utf8(s))
+        } else {
+            Err(UdfError::UnsupportedReturnType(format!("{:?}", d)))
+        }
+    }
+
+}
+
+/// A user-defined scalar function backed by a precompiled Rhai script, bound to named input
+/// columns. Lets a derived column (e.g. `fahrenheit = celsius * 9 / 5 + 32`) be expressed as a
+/// small script instead of compiled Rust.
+pub struct ScalarUdf {
+    name: String,
+    arg_names: Vec<String>,
+    engine: Engine,
+    ast: AST,
+}
+
+impl fmt::Debug for ScalarUdf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ScalarUdf({})", self.name)
+    }
+}
+
+impl ScalarUdf {
+
+    /// Compile `source` once up front; `arg_names` names the columns bound into the script's
+    /// scope, in the same order as the `&[&Array]` passed to `eval`.
+    pub fn compile(name: &str, arg_names: Vec<String>, source: &str) -> Result<Self, UdfError> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(|e| UdfError::Compile(e.to_string()))?;
+        Ok(ScalarUdf { name: name.to_string(), arg_names: arg_names, engine: engine, ast: ast })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Run the script once per row: bind each input column's value into a fresh `Scope` under
+    /// its argument name, evaluate the precompiled AST, and collect the results into an `Array`
+    /// whose variant matches the first row's return type.
+    pub fn eval(&self, columns: &[&Array]) -> Result<Array, UdfError> {
+        if columns.len() != self.arg_names.len() {
+            return Err(UdfError::Eval(format!(
+                "{} expects {} argument(s), got {}", self.name, self.arg_names.len(), columns.len()
+            )));
+        }
+        let row_count = columns.get(0).map(|c| c.len()).unwrap_or(0);
+        let mut results: Vec<Value> = Vec::with_capacity(row_count);
+        for row in 0..row_count {
+            let mut scope = Scope::new();
+            for (arg_name, column) in self.arg_names.iter().zip(columns.iter()) {
+                let dynamic = match column.get_value(row) {
+                    Some(v) => v.to_dynamic(),
+                    None => Dynamic::from(()),
+                };
+                scope.push(arg_name.clone(), dynamic);
+            }
+            let result: Dynamic = self.engine.eval_ast_with_scope(&mut scope, &self.ast)
+                .map_err(|e| match *e {
+                    EvalAltResult::ErrorVariableNotFound(ref name, _) => UdfError::UnknownColumn(name.clone()),
+                    ref other => UdfError::Eval(other.to_string()),
+                })?;
+            results.push(Value::from_dynamic(&result)?);
+        }
+        values_to_array(results)
+    }
+}
+
+/// Collect a script's per-row `Value` results into a densely-typed `Array`, matching the first
+/// row's variant; every row must agree since a UDF isn't expected to change return type per row.
+fn values_to_array(values: Vec<Value>) -> Result<Array, UdfError> {
+    match values.first() {
+        None => Ok(Array::from_bool(vec![])),
+        Some(&Value::Boolean(_)) => {
+            let v = values.iter().map(|v| match v {
+                &Value::Boolean(b) => Ok(b),
+                other => Err(UdfError::UnsupportedReturnType(format!("{:?}", other))),
+            }).collect::<Result<Vec<bool>, UdfError>>()?;
+            Ok(Array::from_bool(v))
+        },
+        Some(&Value::Int64(_)) => {
+            let v = values.iter().map(|v| match v {
+                &Value::Int64(i) => Ok(i),
+                other => Err(UdfError::UnsupportedReturnType(format!("{:?}", other))),
+            }).collect::<Result<Vec<i64>, UdfError>>()?;
+            Ok(Array::from_i64(v))
+        },
+        Some(&Value::Float64(_)) => {
+            let v = values.iter().map(|v| match v {
+                &Value::Float64(f) => Ok(f),
+                other => Err(UdfError::UnsupportedReturnType(format!("{:?}", other))),
+            }).collect::<Result<Vec<f64>, UdfError>>()?;
+            Ok(Array::from_f64(v))
+        },
+        Some(&Value::Utf8(_)) => {
+            let v = values.iter().map(|v| match v {
+                &Value::Utf8(ref s) => Ok(s.clone()),
+                other => Err(UdfError::UnsupportedReturnType(format!("{:?}", other))),
+            }).collect::<Result<Vec<String>, UdfError>>()?;
+            Ok(Array::from_utf8(v))
+        },
+        Some(other) => Err(UdfError::UnsupportedReturnType(format!("{:?}", other))),
+    }
+}
+
+/// A scalar/predicate expression tree, evaluated against a row batch of `Array` columns.
+#[derive(Debug,Clone)]
+pub enum Expr {
+    /// Index of the referenced column within the batch's `Schema`.
+    Column(usize),
+    Literal(Value),
+    BinaryExpr { left: Box<Expr>, op: Operator, right: Box<Expr> },
+    /// A user-defined scalar function call: `udf` is evaluated once per row over `args`.
+    ScalarUdfCall { udf: Rc<ScalarUdf>, args: Vec<Expr> },
+}
+
+impl Expr {
+
+    /// Evaluate this expression against `columns`, the row batch's data (one `Array` per
+    /// `Schema` column, indexed the same way as `Expr::Column`). `WHERE`-clause expressions
+    /// produce an `Array::Boolean` suitable for `Array::filter`. A comparison whose operands have
+    /// no common type, or a failing UDF call, returns `Err` rather than panicking. The arithmetic
+    /// operators and `AND`/`OR` do not coerce their operands and panic unless both already have
+    /// the same supported type.
+    pub fn evaluate(&self, columns: &[Array]) -> Result<Array, String> {
+        match self {
+            &Expr::Column(i) => Ok(columns[i].clone()),
+            &Expr::Literal(ref v) => Ok(Array::BroadcastVariable(v.clone())),
+            &Expr::BinaryExpr { ref left, ref op, ref right } => {
+                let l = left.evaluate(columns)?;
+                let r = right.evaluate(columns)?;
+                match op {
+                    &Operator::Add => Ok(l.add(&r)),
+                    &Operator::Subtract => Ok(l.subtract(&r)),
+                    &Operator::Multiply => Ok(l.multiply(&r)),
+                    &Operator::Divide => Ok(l.divide(&r)),
+                    &Operator::Modulus => Ok(l.modulus(&r)),
+                    &Operator::Power => Ok(l.power(&r)),
+                    &Operator::Eq => l.eq(&r),
+                    &Operator::NotEq => l.not_eq(&r),
+                    &Operator::Lt => l.lt(&r),
+                    &Operator::LtEq => l.lt_eq(&r),
+                    &Operator::Gt => l.gt(&r),
+                    &Operator::GtEq => l.gt_eq(&r),
+                    &Operator::And => Ok(l.and(&r)),
+                    &Operator::Or => Ok(l.or(&r)),
+                }
+            },
+            &Expr::ScalarUdfCall { ref udf, ref args } => {
+                let arg_arrays = args.iter().map(|a| a.evaluate(columns)).collect::<Result<Vec<Array>, String>>()?;
+                let arg_refs: Vec<&Array> = arg_arrays.iter().collect();
+                udf.eval(&arg_refs).map_err(|e| format!("UDF '{}' failed: {:?}", udf.name(), e))
+            }
+        }
+    }
+}
+
+/// A lexical token produced by `tokenize` when scanning an infix expression string.
+#[derive(Debug,Clone,PartialEq)]
+enum Token {
+    Number(String),
+    Identifier(String),
+    StringLiteral(String),
+    Operator(String),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                s.push(chars[i]);
+                i += 1;
+            }
+            i += 1; // closing quote
+            tokens.push(Token::StringLiteral(s));
+        } else if c == '<' || c == '>' || c == '=' {
+            let mut op = c.to_string();
+            if i + 1 < chars.len() && (chars[i+1] == '=' || (c == '<' && chars[i+1] == '>')) {
+                op.push(chars[i+1]);
+                i += 2;
+            } else {
+                i += 1;
+            }
+            tokens.push(Token::Operator(op));
+        } else if c == '+' || c == '-' || c == '*' || c == '/' || c == '%' || c == '^' {
+            tokens.push(Token::Operator(c.to_string()));
+            i += 1;
+        } else if c.is_digit(10) {
+            let mut s = String::new();
+            while i < chars.len() && (chars[i].is_digit(10) || chars[i] == '.') {
+                s.push(chars[i]);
+                i += 1;
+            }
+            tokens.push(Token::Number(s));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut s = String::new();
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                s.push(chars[i]);
+                i += 1;
+            }
+            tokens.push(Token::Identifier(s));
+        } else {
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Parses an infix expression string into an `Expr` tree via operator-precedence climbing,
+/// resolving bare identifiers to `Expr::Column` against `schema`.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    schema: &'a Schema,
+}
+
+impl<'a> Parser<'a> {
+
+    /// Precedence of a binary operator token (higher binds tighter): `Or` lowest, then `And`,
+    /// then comparisons, then `+ -`, then `* / %`, then `^` highest. All are left-associative
+    /// except `^`, which is right-associative (see `parse_expr`'s recursion below).
+    fn precedence(op: &str) -> Option<(u8, Operator)> {
+        match op {
+            "OR" => Some((1, Operator::Or)),
+            "AND" => Some((2, Operator::And)),
+            "=" => Some((3, Operator::Eq)),
+            "<>" => Some((3, Operator::NotEq)),
+            "<" => Some((3, Operator::Lt)),
+            "<=" => Some((3, Operator::LtEq)),
+            ">" => Some((3, Operator::Gt)),
+            ">=" => Some((3, Operator::GtEq)),
+            "+" => Some((4, Operator::Add)),
+            "-" => Some((4, Operator::Subtract)),
+            "*" => Some((5, Operator::Multiply)),
+            "/" => Some((5, Operator::Divide)),
+            "%" => Some((5, Operator::Modulus)),
+            "^" => Some((6, Operator::Power)),
+            _ => None,
+        }
+    }
+
+    /// Whether `op` is right-associative, i.e. `a ^ b ^ c` parses as `a ^ (b ^ c)`. Every other
+    /// operator is left-associative.
+    fn is_right_associative(op: &Operator) -> bool {
+        *op == Operator::Power
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    /// Parse a primary expression, then while the next operator's precedence is >= `min_prec`,
+    /// consume it and recursively parse the right-hand side at `precedence + 1` (left-associative)
+    /// or `precedence` (right-associative, i.e. just `^`).
+    fn parse_expr(&mut self, min_prec: u8) -> Result<Expr, String> {
+        let mut left = self.parse_primary()?;
+        loop {
+            let op_text = match self.peek() {
+                Some(&Token::Operator(ref s)) => s.clone(),
+                Some(&Token::Identifier(ref s)) if s.eq_ignore_ascii_case("and") || s.eq_ignore_ascii_case("or") =>
+                    s.to_uppercase(),
+                _ => break,
+            };
+            let (prec, op) = match Self::precedence(&op_text) {
+                Some(p) => p,
+                None => break,
+            };
+            if prec < min_prec {
+                break;
+            }
+            self.next();
+            let next_min_prec = if Self::is_right_associative(&op) { prec } else { prec + 1 };
+            let right = self.parse_expr(next_min_prec)?;
+            left = Expr::BinaryExpr { left: Box::new(left), op: op, right: Box::new(right) };
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(s)) => {
+                if s.contains('.') {
+                    s.parse::<f64>().map(|n| Expr::Literal(Value::Float64(n))).map_err(|e| e.to_string())
+                } else {
+                    s.parse::<i64>().map(|n| Expr::Literal(Value::Int64(n))).map_err(|e| e.to_string())
+                }
+            },
+            Some(Token::StringLiteral(s)) => Ok(Expr::Literal(Value::Utf8(s))),
+            Some(Token::Identifier(ref s)) if s.eq_ignore_ascii_case("true") => Ok(Expr::Literal(Value::Boolean(true))),
+            Some(Token::Identifier(ref s)) if s.eq_ignore_ascii_case("false") => Ok(Expr::Literal(Value::Boolean(false))),
+            Some(Token::Identifier(name)) => {
+                match self.schema.column(&name) {
+                    Some((i, _)) => Ok(Expr::Column(i)),
+                    None => Err(format!("no column named '{}'", name)),
+                }
+            },
+            Some(Token::LParen) => {
+                let expr = self.parse_expr(0)?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    t => Err(format!("expected ')', found {:?}", t)),
+                }
+            },
+            t => Err(format!("unexpected token: {:?}", t)),
+        }
+    }
+}
+
+/// Parse an infix expression string (e.g. `"a + b * 2 < c AND d = 'x'"`) into an `Expr` tree,
+/// resolving column references against `schema`.
+pub fn parse_expr(input: &str, schema: &Schema) -> Result<Expr, String> {
+    let mut parser = Parser { tokens: tokenize(input), pos: 0, schema: schema };
+    let expr = parser.parse_expr(0)?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("unexpected token after expression: {:?}", t)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ])
+    }
+
+    #[test]
+    fn nulls_propagate_through_add() {
+        let a = Array::Int32(vec![1, 2, 0], Bitmap::from_flags(&[true, true, false]));
+        let b = Array::from_i32(vec![10, 20, 30]);
+        match a.add(&b) {
+            Array::Int32(values, bitmap) => {
+                assert_eq!(vec![11, 22, 30], values);
+                assert_eq!(vec![true, true, false], (0..3).map(|i| bitmap.is_valid(i)).collect::<Vec<bool>>());
+            },
+            other => panic!("expected Int32, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        // 2 ^ 3 ^ 2 must parse as 2 ^ (3 ^ 2), not (2 ^ 3) ^ 2: the top-level right operand
+        // should itself be a `Power` BinaryExpr, not a bare literal.
+        let expr = parse_expr("2 ^ 3 ^ 2", &schema()).unwrap();
+        match expr {
+            Expr::BinaryExpr { op: Operator::Power, ref right, .. } => match **right {
+                Expr::BinaryExpr { op: Operator::Power, .. } => {},
+                ref other => panic!("expected right operand to be a nested `^`, got {:?}", other),
+            },
+            other => panic!("expected a top-level `^` BinaryExpr, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_expr_respects_precedence() {
+        // a + b * 2 should parse as a + (b * 2), not (a + b) * 2.
+        let columns = vec![Array::from_i64(vec![1]), Array::from_i64(vec![3])];
+        let expr = parse_expr("a + b * 2", &schema()).unwrap();
+        match expr.evaluate(&columns).unwrap() {
+            Array::Int64(values, _) => assert_eq!(vec![7], values),
+            other => panic!("expected Int64([7]), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn evaluate_reports_type_mismatch_as_error_not_panic() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let columns = vec![Array::from_utf8(vec!["x".to_string()])];
+        let expr = parse_expr("a = true", &schema).unwrap();
+        assert!(expr.evaluate(&columns).is_err());
+    }
+
+    #[test]
+    fn dictionary_roundtrip_and_eq_fast_path() {
+        let plain = Array::from_utf8(vec!["x".to_string(), "y".to_string(), "x".to_string()]);
+        let dict = plain.encode_dictionary();
+        match dict.eq(&Array::BroadcastVariable(Value::Utf8("x".to_string()))).unwrap() {
+            Array::Boolean(values, _) => assert_eq!(vec![true, false, true], values),
+            other => panic!("expected Boolean, got {:?}", other),
+        }
+        match dict.decode_dictionary() {
+            Array::Utf8(values, _) => assert_eq!(vec!["x", "y", "x"], values),
+            other => panic!("expected Utf8, got {:?}", other),
+        }
+    }
 }
@@ -20,12 +20,20 @@ use std::rc::Rc;
 use std::sync::Arc;
 use std::str;
 
-use arrow::array::{Array, ArrayRef, Int32Array, Float64Array, BinaryArray};
+use arrow::array::{
+    Array, ArrayRef, Int8Array, Int16Array, Int32Array, Int64Array, UInt8Array, UInt16Array,
+    UInt32Array, UInt64Array, Float32Array, Float64Array, BinaryArray,
+};
 use arrow::array_ops;
-use arrow::builder::{ArrayBuilder, Int32Builder, Float64Builder};
+use arrow::builder::{
+    ArrayBuilder, BooleanBuilder, BinaryBuilder, Int8Builder, Int16Builder, Int32Builder,
+    Int64Builder, UInt8Builder, UInt16Builder, UInt32Builder, UInt64Builder, Float32Builder,
+    Float64Builder,
+};
 use arrow::datatypes::{Field, Schema, DataType};
 use arrow::record_batch::RecordBatch;
 
+use super::context::ExecutionContext;
 use super::error::{Result, ExecutionError};
 use super::expression::{RuntimeExpr, AggregateType};
 use crate::logicalplan::ScalarValue;
@@ -40,6 +48,10 @@ pub struct AggregateRelation {
     input: Rc<RefCell<Relation>>,
     group_expr: Vec<RuntimeExpr>,
     aggr_expr: Vec<RuntimeExpr>,
+    /// Holds the `ExecutionContext` the plan was compiled against so that `create_accumulators`
+    /// can look up a user-defined aggregate function registered via `register_udaf` when it
+    /// encounters an aggregate expression that isn't one of the built-ins.
+    context: Rc<ExecutionContext>,
 }
 
 
@@ -49,12 +61,14 @@ impl AggregateRelation {
         input: Rc<RefCell<Relation>>,
         group_expr: Vec<RuntimeExpr>,
         aggr_expr: Vec<RuntimeExpr>,
+        context: Rc<ExecutionContext>,
     ) -> Self {
         AggregateRelation {
             schema,
             input,
             group_expr,
             aggr_expr,
+            context,
         }
     }
 }
@@ -75,14 +89,75 @@ enum GroupByScalar {
     Utf8(String),
 }
 
-/// Common trait for all aggregation functions
-trait AggregateFunction {
+/// Common trait for all aggregation functions. Public so that code outside this module can
+/// implement a user-defined aggregate and hand it to `ExecutionContext::register_udaf`.
+pub trait AggregateFunction {
     fn accumulate_scalar(&mut self, value: &Option<ScalarValue>);
     fn accumulate_array(&mut self, array: ArrayRef);
     fn result(&self) -> &Option<ScalarValue>;
     fn data_type(&self) -> &DataType;
 }
 
+/// Builds a fresh accumulator for a user-defined aggregate function given the data type of its
+/// argument. Registered against a name with `ExecutionContext::register_udaf` (see `context.rs`);
+/// `create_accumulators` consults the registry by name when it meets an `AggregateType::Custom`.
+pub type UdafFactory = Rc<Fn(&DataType) -> Rc<RefCell<AggregateFunction>>>;
+
+/// Which argument data types an aggregate function accepts.
+enum TypeSignature {
+    /// Any of the numeric types (signed/unsigned integers, floats).
+    AnyNumeric,
+    /// Any input type at all, e.g. MIN/MAX which also work over Utf8.
+    Any,
+}
+
+/// Declares the argument types an aggregate function accepts, and derives its output type from
+/// the actual argument type. This is the one place that decides whether an argument is
+/// acceptable and how the output widens (e.g. SUM promoting Int32 to Int64), instead of that
+/// logic being scattered across `accumulate_scalar`, `array_min`/`array_max`, and the
+/// result-builder match statements. Arguments are not converted before accumulation: SUM and AVG
+/// accumulators are built with the actual argument type and read their input in its native width.
+struct Signature {
+    type_signature: TypeSignature,
+    coerce: fn(&DataType) -> DataType,
+}
+
+impl Signature {
+    fn any_numeric(coerce: fn(&DataType) -> DataType) -> Self {
+        Signature { type_signature: TypeSignature::AnyNumeric, coerce }
+    }
+
+    fn any_with(coerce: fn(&DataType) -> DataType) -> Self {
+        Signature { type_signature: TypeSignature::Any, coerce }
+    }
+
+    fn any() -> Self {
+        Self::any_with(|dt| dt.clone())
+    }
+
+    /// Validate `actual` against this signature and return `(coerced_input_type, output_type)`.
+    /// Both elements are the same type, the result of this signature's `coerce` function.
+    fn resolve(&self, actual: &DataType) -> Result<(DataType, DataType)> {
+        match &self.type_signature {
+            TypeSignature::AnyNumeric if !is_numeric(actual) => {
+                return Err(ExecutionError::General(format!("{:?} is not a numeric type", actual)));
+            }
+            _ => {}
+        }
+        let coerced = (self.coerce)(actual);
+        Ok((coerced.clone(), coerced))
+    }
+}
+
+fn is_numeric(dt: &DataType) -> bool {
+    match dt {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 |
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 |
+        DataType::Float32 | DataType::Float64 => true,
+        _ => false,
+    }
+}
+
 struct MinFunction {
     data_type: DataType,
     value: Option<ScalarValue>,
@@ -92,6 +167,11 @@ impl MinFunction {
     fn new(data_type: &DataType) -> Self {
         Self { data_type: data_type.clone(), value: None }
     }
+
+    /// MIN accepts any input type unchanged, including Utf8 (compared lexicographically).
+    fn signature() -> Signature {
+        Signature::any()
+    }
 }
 
 impl AggregateFunction for MinFunction {
@@ -111,12 +191,17 @@ impl AggregateFunction for MinFunction {
                 (Some(ScalarValue::Int64(a)), Some(ScalarValue::Int64(b))) => Some(ScalarValue::Int64(*a.min(b))),
                 (Some(ScalarValue::Float32(a)), Some(ScalarValue::Float32(b))) => Some(ScalarValue::Float32(a.min(*b))),
                 (Some(ScalarValue::Float64(a)), Some(ScalarValue::Float64(b))) => Some(ScalarValue::Float64(a.min(*b))),
+                (Some(ScalarValue::Utf8(a)), Some(ScalarValue::Utf8(b))) => Some(ScalarValue::Utf8(a.min(b).clone())),
                 _ => panic!("unsupported data type for MIN")
             }
         }
     }
 
     fn accumulate_array(&mut self, array: ArrayRef) {
+        // compute the array's min in one vectorized pass, then merge it into the running value
+        // using the same scalar combination logic, instead of a per-row match + downcast
+        let batch_min = array_min(array, &self.data_type).expect("failed to compute array min");
+        self.accumulate_scalar(&batch_min);
     }
 
     fn result(&self) -> &Option<ScalarValue> {
@@ -137,6 +222,11 @@ impl MaxFunction {
     fn new(data_type: &DataType) -> Self {
         Self { data_type: data_type.clone(), value: None }
     }
+
+    /// MAX accepts any input type unchanged, including Utf8 (compared lexicographically).
+    fn signature() -> Signature {
+        Signature::any()
+    }
 }
 
 impl AggregateFunction for MaxFunction {
@@ -156,12 +246,15 @@ impl AggregateFunction for MaxFunction {
                 (Some(ScalarValue::Int64(a)), Some(ScalarValue::Int64(b))) => Some(ScalarValue::Int64(*a.max(b))),
                 (Some(ScalarValue::Float32(a)), Some(ScalarValue::Float32(b))) => Some(ScalarValue::Float32(a.max(*b))),
                 (Some(ScalarValue::Float64(a)), Some(ScalarValue::Float64(b))) => Some(ScalarValue::Float64(a.max(*b))),
+                (Some(ScalarValue::Utf8(a)), Some(ScalarValue::Utf8(b))) => Some(ScalarValue::Utf8(a.max(b).clone())),
                 _ => panic!("unsupported data type for MAX")
             }
         }
     }
 
     fn accumulate_array(&mut self, array: ArrayRef) {
+        let batch_max = array_max(array, &self.data_type).expect("failed to compute array max");
+        self.accumulate_scalar(&batch_max);
     }
 
     fn result(&self) -> &Option<ScalarValue> {
@@ -173,102 +266,477 @@ impl AggregateFunction for MaxFunction {
     }
 }
 
-struct AccumulatorSet {
-    aggr_values: Vec<Rc<RefCell<AggregateFunction>>>
+/// Promote a numeric data type to the widest type that can hold a running SUM without
+/// overflowing more often than the input type already would (Int8/16/32/64 -> Int64,
+/// UInt8/16/32/64 -> UInt64, Float32 -> Float64).
+fn sum_data_type(dt: &DataType) -> DataType {
+    match dt {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => DataType::Int64,
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => DataType::UInt64,
+        DataType::Float32 | DataType::Float64 => DataType::Float64,
+        other => other.clone(),
+    }
+}
+
+struct CountFunction {
+    data_type: DataType,
+    value: Option<ScalarValue>,
+}
+
+impl CountFunction {
+    fn new() -> Self {
+        Self { data_type: DataType::Int64, value: Some(ScalarValue::Int64(0)) }
+    }
+
+    /// COUNT accepts any input type and always outputs Int64, regardless of the argument.
+    fn signature() -> Signature {
+        Signature::any_with(|_| DataType::Int64)
+    }
+}
+
+impl AggregateFunction for CountFunction {
+
+    fn accumulate_scalar(&mut self, value: &Option<ScalarValue>) {
+        if value.is_some() {
+            self.value = match self.value {
+                Some(ScalarValue::Int64(n)) => Some(ScalarValue::Int64(n + 1)),
+                _ => panic!("COUNT accumulator corrupted")
+            }
+        }
+    }
+
+    fn accumulate_array(&mut self, array: ArrayRef) {
+        // COUNT needs no type dispatch at all: every non-null slot counts, regardless of type
+        let n = array.len() - array.null_count();
+        self.value = match self.value {
+            Some(ScalarValue::Int64(c)) => Some(ScalarValue::Int64(c + n as i64)),
+            _ => panic!("COUNT accumulator corrupted")
+        }
+    }
+
+    fn result(&self) -> &Option<ScalarValue> {
+        &self.value
+    }
+
+    fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+}
+
+struct SumFunction {
+    /// The actual argument type (e.g. `Int32`), needed to downcast the native-width array
+    /// `accumulate_array` receives -- see `AvgFunction`'s identical `arg_type`/`data_type` split.
+    arg_type: DataType,
+    data_type: DataType,
+    value: Option<ScalarValue>,
 }
 
-impl AccumulatorSet {
-    fn accumulate_scalar(&mut self, i: usize, value: Option<ScalarValue>) {
-        println!("accumulate_scalar {:?}", value);
-        self.aggr_values[i].borrow_mut().accumulate_scalar(&value);
+impl SumFunction {
+    fn new(arg_type: &DataType) -> Self {
+        Self { arg_type: arg_type.clone(), data_type: sum_data_type(arg_type), value: None }
+    }
 
+    /// SUM accepts any numeric type and widens it via `sum_data_type` (Int32 -> Int64,
+    /// Float32 -> Float64) so the running total has headroom beyond a single input value.
+    fn signature() -> Signature {
+        Signature::any_numeric(sum_data_type)
     }
 }
 
-/// Create an initial aggregate entry
-fn create_accumulators(aggr_expr: &Vec<RuntimeExpr>) -> AccumulatorSet {
+impl AggregateFunction for SumFunction {
+
+    fn accumulate_scalar(&mut self, value: &Option<ScalarValue>) {
+        // `self.data_type` is always the widened type from `sum_data_type` (Int64 or UInt64 for
+        // any signed/unsigned integer input, Float64 for any float input), so the running total
+        // (`self.value`) is always one of those three variants; only the incoming `value` varies
+        // across the narrower widths a batch can produce.
+        self.value = match (&self.data_type, &self.value, value) {
+            (_, _, None) => return,
+            (DataType::Int64, None, Some(ScalarValue::Int8(b))) => Some(ScalarValue::Int64(*b as i64)),
+            (DataType::Int64, None, Some(ScalarValue::Int16(b))) => Some(ScalarValue::Int64(*b as i64)),
+            (DataType::Int64, None, Some(ScalarValue::Int32(b))) => Some(ScalarValue::Int64(*b as i64)),
+            (DataType::Int64, None, Some(ScalarValue::Int64(b))) => Some(ScalarValue::Int64(*b)),
+            (DataType::Int64, Some(ScalarValue::Int64(a)), Some(ScalarValue::Int8(b))) => Some(ScalarValue::Int64(a + *b as i64)),
+            (DataType::Int64, Some(ScalarValue::Int64(a)), Some(ScalarValue::Int16(b))) => Some(ScalarValue::Int64(a + *b as i64)),
+            (DataType::Int64, Some(ScalarValue::Int64(a)), Some(ScalarValue::Int32(b))) => Some(ScalarValue::Int64(a + *b as i64)),
+            (DataType::Int64, Some(ScalarValue::Int64(a)), Some(ScalarValue::Int64(b))) => Some(ScalarValue::Int64(a + b)),
+            (DataType::UInt64, None, Some(ScalarValue::UInt8(b))) => Some(ScalarValue::UInt64(*b as u64)),
+            (DataType::UInt64, None, Some(ScalarValue::UInt16(b))) => Some(ScalarValue::UInt64(*b as u64)),
+            (DataType::UInt64, None, Some(ScalarValue::UInt32(b))) => Some(ScalarValue::UInt64(*b as u64)),
+            (DataType::UInt64, None, Some(ScalarValue::UInt64(b))) => Some(ScalarValue::UInt64(*b)),
+            (DataType::UInt64, Some(ScalarValue::UInt64(a)), Some(ScalarValue::UInt8(b))) => Some(ScalarValue::UInt64(a + *b as u64)),
+            (DataType::UInt64, Some(ScalarValue::UInt64(a)), Some(ScalarValue::UInt16(b))) => Some(ScalarValue::UInt64(a + *b as u64)),
+            (DataType::UInt64, Some(ScalarValue::UInt64(a)), Some(ScalarValue::UInt32(b))) => Some(ScalarValue::UInt64(a + *b as u64)),
+            (DataType::UInt64, Some(ScalarValue::UInt64(a)), Some(ScalarValue::UInt64(b))) => Some(ScalarValue::UInt64(a + b)),
+            (DataType::Float64, None, Some(ScalarValue::Float32(b))) => Some(ScalarValue::Float64(*b as f64)),
+            (DataType::Float64, None, Some(ScalarValue::Float64(b))) => Some(ScalarValue::Float64(*b)),
+            (DataType::Float64, Some(ScalarValue::Float64(a)), Some(ScalarValue::Float32(b))) => Some(ScalarValue::Float64(a + *b as f64)),
+            (DataType::Float64, Some(ScalarValue::Float64(a)), Some(ScalarValue::Float64(b))) => Some(ScalarValue::Float64(a + b)),
+            _ => panic!("unsupported data type for SUM")
+        }
+    }
+
+    fn accumulate_array(&mut self, array: ArrayRef) {
+        // `array` is still in its native width (e.g. an Int32Array for an Int32 column); only the
+        // running total in `self.value`/`self.data_type` is widened, so the array must be summed
+        // using `arg_type`, not `data_type` (same reasoning as `AvgFunction::accumulate_array`).
+        let batch_sum = array_sum(array, &self.arg_type).expect("failed to compute array sum");
+        self.accumulate_scalar(&batch_sum);
+    }
+
+    fn result(&self) -> &Option<ScalarValue> {
+        &self.value
+    }
+
+    fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+}
+
+/// AVG keeps a running sum and a running non-null count rather than rewriting `AVG(x)` as
+/// `SUM(x) / COUNT(x)` in the planner, so the intermediate precision lives in one place. The
+/// mean of integers is fractional, so the output is always Float64 regardless of the input type.
+struct AvgFunction {
+    arg_type: DataType,
+    data_type: DataType,
+    sum: Option<f64>,
+    count: i64,
+    value: Option<ScalarValue>,
+}
+
+impl AvgFunction {
+    fn new(arg_type: &DataType) -> Self {
+        Self {
+            arg_type: arg_type.clone(),
+            data_type: DataType::Float64,
+            sum: None,
+            count: 0,
+            value: None,
+        }
+    }
+
+    /// AVG accepts any numeric type but always outputs Float64, since the mean of integers is
+    /// fractional.
+    fn signature() -> Signature {
+        Signature::any_numeric(|_| DataType::Float64)
+    }
+
+    fn update(&mut self, sum: f64, count: i64) {
+        self.sum = Some(self.sum.unwrap_or(0.0) + sum);
+        self.count += count;
+        self.value = Some(ScalarValue::Float64(self.sum.unwrap() / self.count as f64));
+    }
+}
+
+impl AggregateFunction for AvgFunction {
+
+    fn accumulate_scalar(&mut self, value: &Option<ScalarValue>) {
+        let n = match value {
+            Some(ScalarValue::Int8(n)) => *n as f64,
+            Some(ScalarValue::Int16(n)) => *n as f64,
+            Some(ScalarValue::Int32(n)) => *n as f64,
+            Some(ScalarValue::Int64(n)) => *n as f64,
+            Some(ScalarValue::UInt8(n)) => *n as f64,
+            Some(ScalarValue::UInt16(n)) => *n as f64,
+            Some(ScalarValue::UInt32(n)) => *n as f64,
+            Some(ScalarValue::UInt64(n)) => *n as f64,
+            Some(ScalarValue::Float32(n)) => *n as f64,
+            Some(ScalarValue::Float64(n)) => *n,
+            Some(_) => panic!("unsupported data type for AVG"),
+            None => return,
+        };
+        self.update(n, 1);
+    }
+
+    fn accumulate_array(&mut self, array: ArrayRef) {
+        let valid = array.len() - array.null_count();
+        if valid == 0 {
+            return;
+        }
+        // Convert each value of the batch to f64 and sum in f64, since AVG's running sum is always
+        // fractional regardless of input type.
+        let batch_sum: f64 = match self.arg_type {
+            DataType::Int8 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Int8Array>().unwrap().value(i) as f64).sum(),
+            DataType::Int16 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Int16Array>().unwrap().value(i) as f64).sum(),
+            DataType::Int32 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Int32Array>().unwrap().value(i) as f64).sum(),
+            DataType::Int64 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Int64Array>().unwrap().value(i) as f64).sum(),
+            DataType::UInt8 => (0..array.len()).map(|i| array.as_any().downcast_ref::<UInt8Array>().unwrap().value(i) as f64).sum(),
+            DataType::UInt16 => (0..array.len()).map(|i| array.as_any().downcast_ref::<UInt16Array>().unwrap().value(i) as f64).sum(),
+            DataType::UInt32 => (0..array.len()).map(|i| array.as_any().downcast_ref::<UInt32Array>().unwrap().value(i) as f64).sum(),
+            DataType::UInt64 => (0..array.len()).map(|i| array.as_any().downcast_ref::<UInt64Array>().unwrap().value(i) as f64).sum(),
+            DataType::Float32 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Float32Array>().unwrap().value(i) as f64).sum(),
+            DataType::Float64 => (0..array.len()).map(|i| array.as_any().downcast_ref::<Float64Array>().unwrap().value(i)).sum(),
+            _ => panic!("unsupported data type for AVG"),
+        };
+        self.update(batch_sum, valid as i64);
+    }
+
+    fn result(&self) -> &Option<ScalarValue> {
+        &self.value
+    }
+
+    fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+}
+
+struct AccumulatorSet {
+    aggr_values: Vec<Rc<RefCell<AggregateFunction>>>
+}
+
+/// Create an initial aggregate entry. Built-in functions (MIN/MAX/COUNT/SUM/AVG) first resolve
+/// their argument's data type against their `Signature`, so SUM and AVG reject a non-numeric
+/// argument here with a proper `Err`. MIN, MAX and COUNT accept every argument type; MIN/MAX
+/// over a type other than the numerics and Utf8 panic when the first array is accumulated. Any other
+/// name is looked up in `context`'s UDAF registry so callers can plug in a custom
+/// `AggregateFunction` without this function knowing about it ahead of time.
+fn create_accumulators(aggr_expr: &Vec<RuntimeExpr>, context: &ExecutionContext) -> Result<AccumulatorSet> {
     let functions = aggr_expr
         .iter()
         .map(|e| match e {
             RuntimeExpr::AggregateFunction { ref f, ref t, .. } => match f {
-                AggregateType::Min => Rc::new(RefCell::new(MinFunction::new(t))) as Rc<RefCell<AggregateFunction>>,
-                AggregateType::Max => Rc::new(RefCell::new(MaxFunction::new(t))) as Rc<RefCell<AggregateFunction>>,
+                AggregateType::Min => {
+                    let (coerced, _) = MinFunction::signature().resolve(t)?;
+                    Ok(Rc::new(RefCell::new(MinFunction::new(&coerced))) as Rc<RefCell<AggregateFunction>>)
+                }
+                AggregateType::Max => {
+                    let (coerced, _) = MaxFunction::signature().resolve(t)?;
+                    Ok(Rc::new(RefCell::new(MaxFunction::new(&coerced))) as Rc<RefCell<AggregateFunction>>)
+                }
+                AggregateType::Count => {
+                    CountFunction::signature().resolve(t)?;
+                    Ok(Rc::new(RefCell::new(CountFunction::new())) as Rc<RefCell<AggregateFunction>>)
+                }
+                AggregateType::Sum => {
+                    // SUM's signature widens its output type (e.g. Int32 -> Int64), but the
+                    // accumulator still needs the actual argument type to downcast the incoming
+                    // array, so the original `t` is passed through rather than the resolved type.
+                    SumFunction::signature().resolve(t)?;
+                    Ok(Rc::new(RefCell::new(SumFunction::new(t))) as Rc<RefCell<AggregateFunction>>)
+                }
+                AggregateType::Avg => {
+                    // AVG's signature always coerces to Float64 output, but the accumulator
+                    // still needs the *actual* argument type to downcast the incoming array, so
+                    // the original `t` is passed through rather than the resolved type.
+                    AvgFunction::signature().resolve(t)?;
+                    Ok(Rc::new(RefCell::new(AvgFunction::new(t))) as Rc<RefCell<AggregateFunction>>)
+                }
+                AggregateType::Custom(name) => match context.udaf(name) {
+                    Some(factory) => Ok(factory(t)),
+                    None => Err(ExecutionError::General(format!("no user-defined aggregate function registered for '{}'", name))),
+                },
                 _ => panic!("unsupported aggregate function"),
             },
             _ => panic!("invalid aggregate expression"),
         })
-        .collect();
+        .collect::<Result<Vec<Rc<RefCell<AggregateFunction>>>>>()?;
 
-    AccumulatorSet {
+    Ok(AccumulatorSet {
         aggr_values: functions,
-    }
+    })
+}
+
+/// Resolve the output `DataType` a built-in aggregate function will produce for an argument of
+/// type `t`, without constructing an accumulator. Returns `None` for `AggregateType::Custom`,
+/// whose output type is only known once `context.udaf` has built a concrete accumulator.
+fn aggregate_output_type(f: &AggregateType, t: &DataType) -> Result<Option<DataType>> {
+    let signature = match f {
+        AggregateType::Min => MinFunction::signature(),
+        AggregateType::Max => MaxFunction::signature(),
+        AggregateType::Count => CountFunction::signature(),
+        AggregateType::Sum => SumFunction::signature(),
+        AggregateType::Avg => AvgFunction::signature(),
+        _ => return Ok(None),
+    };
+    let (_, output) = signature.resolve(t)?;
+    Ok(Some(output))
 }
 
 //TODO macros to make this code less verbose
 
+/// Expand a `downcast_ref` + `array_ops` reduction arm for every primitive Arrow numeric type,
+/// so MIN/MAX don't need one hand-written match arm per width. Utf8 isn't a numeric array so it
+/// is matched separately by the caller.
+macro_rules! reduce_numeric {
+    ($array:expr, $dt:expr, $reduce:path, $err:expr) => {
+        match $dt {
+            DataType::Int8 => Ok($reduce($array.as_any().downcast_ref::<Int8Array>().unwrap()).map(ScalarValue::Int8)),
+            DataType::Int16 => Ok($reduce($array.as_any().downcast_ref::<Int16Array>().unwrap()).map(ScalarValue::Int16)),
+            DataType::Int32 => Ok($reduce($array.as_any().downcast_ref::<Int32Array>().unwrap()).map(ScalarValue::Int32)),
+            DataType::Int64 => Ok($reduce($array.as_any().downcast_ref::<Int64Array>().unwrap()).map(ScalarValue::Int64)),
+            DataType::UInt8 => Ok($reduce($array.as_any().downcast_ref::<UInt8Array>().unwrap()).map(ScalarValue::UInt8)),
+            DataType::UInt16 => Ok($reduce($array.as_any().downcast_ref::<UInt16Array>().unwrap()).map(ScalarValue::UInt16)),
+            DataType::UInt32 => Ok($reduce($array.as_any().downcast_ref::<UInt32Array>().unwrap()).map(ScalarValue::UInt32)),
+            DataType::UInt64 => Ok($reduce($array.as_any().downcast_ref::<UInt64Array>().unwrap()).map(ScalarValue::UInt64)),
+            DataType::Float32 => Ok($reduce($array.as_any().downcast_ref::<Float32Array>().unwrap()).map(ScalarValue::Float32)),
+            DataType::Float64 => Ok($reduce($array.as_any().downcast_ref::<Float64Array>().unwrap()).map(ScalarValue::Float64)),
+            _ => Err($err)
+        }
+    };
+}
+
 fn array_min(array: ArrayRef, dt: &DataType) -> Result<Option<ScalarValue>> {
     match dt {
-//        DataType::Int32 => {
-//            let value = array_ops::min(array.as_any().downcast_ref::<Int32Array>().unwrap());
-//            Ok(Arc::new(Int32Array::from(vec![value])) as ArrayRef)
-//        }
-        DataType::Float64 => {
-            match array_ops::min(array.as_any().downcast_ref::<Float64Array>().unwrap()) {
-                Some(n) => Ok(Some(ScalarValue::Float64(n))),
-                None => Ok(None)
-            }
-        }
-        //TODO support all types
-        _ => Err(ExecutionError::NotImplemented("Unsupported data type for MIN".to_string()))
+        DataType::Utf8 => array_min_max_utf8(array, true),
+        _ => reduce_numeric!(array, dt, array_ops::min, ExecutionError::NotImplemented(format!("Unsupported data type for MIN: {:?}", dt))),
     }
 }
 
 fn array_max(array: ArrayRef, dt: &DataType) -> Result<Option<ScalarValue>> {
     match dt {
-//        DataType::Int32 => {
-//            let value = array_ops::max(array.as_any().downcast_ref::<Int32Array>().unwrap());
-//            Ok(Arc::new(Int32Array::from(vec![value])) as ArrayRef)
-//        }
-        DataType::Float64 => {
-            match array_ops::max(array.as_any().downcast_ref::<Float64Array>().unwrap()) {
-                Some(n) => Ok(Some(ScalarValue::Float64(n))),
-                None => Ok(None)
-            }
+        DataType::Utf8 => array_min_max_utf8(array, false),
+        _ => reduce_numeric!(array, dt, array_ops::max, ExecutionError::NotImplemented(format!("Unsupported data type for MAX: {:?}", dt))),
+    }
+}
+
+/// MIN/MAX over Utf8 compare lexicographically; not a numeric reduction so it can't share
+/// `reduce_numeric!`. `want_min` picks MIN vs MAX so both callers can share the downcast.
+fn array_min_max_utf8(array: ArrayRef, want_min: bool) -> Result<Option<ScalarValue>> {
+    let a = array.as_any().downcast_ref::<BinaryArray>().unwrap();
+    let values = (0..a.len()).map(|i| str::from_utf8(a.get_value(i)).unwrap());
+    let result = if want_min { values.min() } else { values.max() };
+    Ok(result.map(|s| ScalarValue::Utf8(s.to_string())))
+}
+
+/// `dt` here is the *input* array's data type (e.g. `Int32`), not the accumulator's widened
+/// `sum_data_type` (e.g. `Int64`) -- the result is produced in the narrower native width and
+/// then widened by `accumulate_scalar`'s match on `self.data_type`, same as `array_min`/`array_max`.
+fn array_sum(array: ArrayRef, dt: &DataType) -> Result<Option<ScalarValue>> {
+    if array.len() == 0 {
+        return Ok(None);
+    }
+    reduce_numeric!(array, dt, array_ops::sum, ExecutionError::NotImplemented(format!("Unsupported data type for SUM: {:?}", dt)))
+}
+
+/// Expand a `downcast_ref` + gather-by-index arm for every primitive Arrow numeric type.
+macro_rules! take_numeric {
+    ($array:expr, $indices:expr, $dt:expr, $arr_ty:ty, $native:ty) => {{
+        let a = $array.as_any().downcast_ref::<$arr_ty>().unwrap();
+        Arc::new(<$arr_ty>::from($indices.iter().map(|&i| a.value(i)).collect::<Vec<$native>>())) as ArrayRef
+    }};
+}
+
+/// Take a sub-array made up of the rows at `indices`, used to slice a batch's column into one
+/// sub-array per GROUP BY key so each group's worth of rows can be fed to `accumulate_array` in
+/// a single call instead of looking up and updating one accumulator per row.
+fn take(array: &ArrayRef, indices: &[usize], dt: &DataType) -> ArrayRef {
+    match dt {
+        DataType::Int8 => take_numeric!(array, indices, dt, Int8Array, i8),
+        DataType::Int16 => take_numeric!(array, indices, dt, Int16Array, i16),
+        DataType::Int32 => take_numeric!(array, indices, dt, Int32Array, i32),
+        DataType::Int64 => take_numeric!(array, indices, dt, Int64Array, i64),
+        DataType::UInt8 => take_numeric!(array, indices, dt, UInt8Array, u8),
+        DataType::UInt16 => take_numeric!(array, indices, dt, UInt16Array, u16),
+        DataType::UInt32 => take_numeric!(array, indices, dt, UInt32Array, u32),
+        DataType::UInt64 => take_numeric!(array, indices, dt, UInt64Array, u64),
+        DataType::Float32 => take_numeric!(array, indices, dt, Float32Array, f32),
+        DataType::Float64 => take_numeric!(array, indices, dt, Float64Array, f64),
+        DataType::Utf8 => {
+            let a = array.as_any().downcast_ref::<BinaryArray>().unwrap();
+            Arc::new(BinaryArray::from(indices.iter().map(|&i| a.get_value(i)).collect::<Vec<&[u8]>>())) as ArrayRef
         }
-        //TODO support all types
-        _ => Err(ExecutionError::NotImplemented("Unsupported data type for MAX".to_string()))
-    }
-}
-
-fn update_accumulators(batch: &RecordBatch, row: usize, accumulator_set: &mut AccumulatorSet, aggr_expr: &Vec<RuntimeExpr>) {
-    // update the accumulators
-    for j in 0..accumulator_set.aggr_values.len() {
-        match &aggr_expr[j] {
-            RuntimeExpr::AggregateFunction { f, args, t, .. } => {
-
-                // evaluate argument to aggregate function
-                match args[0](&batch) {
-                    Ok(array) => {
-                        let value: Option<ScalarValue> = match t {
-                            DataType::Int32 => {
-                                let z = array.as_any().downcast_ref::<Int32Array>().unwrap();
-                                Some(ScalarValue::Int32(z.value(row)))
-                            }
-                            DataType::Float64 => {
-                                let z = array.as_any().downcast_ref::<Float64Array>().unwrap();
-                                Some(ScalarValue::Float64(z.value(row)))
-                            }
-                            _ => panic!()
-                        };
-                        accumulator_set.accumulate_scalar(j, value);
-                    }
-                    _ => panic!()
-                }
-            }
-            _ => panic!()
+        _ => unimplemented!("take: unsupported data type {:?}", dt)
+    }
+}
+
+/// Builder that can accumulate either GROUP BY key values or aggregate results for a single
+/// output column, dispatching to the concrete Arrow builder that matches the column's data type.
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Int8(Int8Builder),
+    Int16(Int16Builder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    UInt8(UInt8Builder),
+    UInt16(UInt16Builder),
+    UInt32(UInt32Builder),
+    UInt64(UInt64Builder),
+    Float32(Float32Builder),
+    Float64(Float64Builder),
+    Utf8(BinaryBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(dt: &DataType, capacity: usize) -> Self {
+        match dt {
+            DataType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::new(capacity)),
+            DataType::Int8 => ColumnBuilder::Int8(Int8Builder::new(capacity)),
+            DataType::Int16 => ColumnBuilder::Int16(Int16Builder::new(capacity)),
+            DataType::Int32 => ColumnBuilder::Int32(Int32Builder::new(capacity)),
+            DataType::Int64 => ColumnBuilder::Int64(Int64Builder::new(capacity)),
+            DataType::UInt8 => ColumnBuilder::UInt8(UInt8Builder::new(capacity)),
+            DataType::UInt16 => ColumnBuilder::UInt16(UInt16Builder::new(capacity)),
+            DataType::UInt32 => ColumnBuilder::UInt32(UInt32Builder::new(capacity)),
+            DataType::UInt64 => ColumnBuilder::UInt64(UInt64Builder::new(capacity)),
+            DataType::Float32 => ColumnBuilder::Float32(Float32Builder::new(capacity)),
+            DataType::Float64 => ColumnBuilder::Float64(Float64Builder::new(capacity)),
+            DataType::Utf8 => ColumnBuilder::Utf8(BinaryBuilder::new(capacity)),
+            other => panic!("unsupported GROUP BY / aggregate output type {:?}", other),
+        }
+    }
+
+    fn push_group_value(&mut self, value: &GroupByScalar) -> Result<()> {
+        match (self, value) {
+            (ColumnBuilder::Boolean(b), GroupByScalar::Boolean(v)) => b.push(*v),
+            (ColumnBuilder::Int8(b), GroupByScalar::Int8(v)) => b.push(*v),
+            (ColumnBuilder::Int16(b), GroupByScalar::Int16(v)) => b.push(*v),
+            (ColumnBuilder::Int32(b), GroupByScalar::Int32(v)) => b.push(*v),
+            (ColumnBuilder::Int64(b), GroupByScalar::Int64(v)) => b.push(*v),
+            (ColumnBuilder::UInt8(b), GroupByScalar::UInt8(v)) => b.push(*v),
+            (ColumnBuilder::UInt16(b), GroupByScalar::UInt16(v)) => b.push(*v),
+            (ColumnBuilder::UInt32(b), GroupByScalar::UInt32(v)) => b.push(*v),
+            (ColumnBuilder::UInt64(b), GroupByScalar::UInt64(v)) => b.push(*v),
+            (ColumnBuilder::Utf8(b), GroupByScalar::Utf8(v)) => b.push(v.as_bytes()),
+            (_, scalar) => panic!("GROUP BY key {:?} does not match output column type", scalar),
+        }
+    }
+
+    fn push_aggregate_result(&mut self, value: &Option<ScalarValue>) -> Result<()> {
+        match (self, value) {
+            (ColumnBuilder::Boolean(b), Some(ScalarValue::Boolean(n))) => b.push(*n),
+            (ColumnBuilder::Boolean(b), None) => b.push_null(),
+            (ColumnBuilder::Int8(b), Some(ScalarValue::Int8(n))) => b.push(*n),
+            (ColumnBuilder::Int8(b), None) => b.push_null(),
+            (ColumnBuilder::Int16(b), Some(ScalarValue::Int16(n))) => b.push(*n),
+            (ColumnBuilder::Int16(b), None) => b.push_null(),
+            (ColumnBuilder::Int32(b), Some(ScalarValue::Int32(n))) => b.push(*n),
+            (ColumnBuilder::Int32(b), None) => b.push_null(),
+            (ColumnBuilder::Int64(b), Some(ScalarValue::Int64(n))) => b.push(*n),
+            (ColumnBuilder::Int64(b), None) => b.push_null(),
+            (ColumnBuilder::UInt8(b), Some(ScalarValue::UInt8(n))) => b.push(*n),
+            (ColumnBuilder::UInt8(b), None) => b.push_null(),
+            (ColumnBuilder::UInt16(b), Some(ScalarValue::UInt16(n))) => b.push(*n),
+            (ColumnBuilder::UInt16(b), None) => b.push_null(),
+            (ColumnBuilder::UInt32(b), Some(ScalarValue::UInt32(n))) => b.push(*n),
+            (ColumnBuilder::UInt32(b), None) => b.push_null(),
+            (ColumnBuilder::UInt64(b), Some(ScalarValue::UInt64(n))) => b.push(*n),
+            (ColumnBuilder::UInt64(b), None) => b.push_null(),
+            (ColumnBuilder::Float32(b), Some(ScalarValue::Float32(n))) => b.push(*n),
+            (ColumnBuilder::Float32(b), None) => b.push_null(),
+            (ColumnBuilder::Float64(b), Some(ScalarValue::Float64(n))) => b.push(*n),
+            (ColumnBuilder::Float64(b), None) => b.push_null(),
+            (ColumnBuilder::Utf8(b), Some(ScalarValue::Utf8(n))) => b.push(n.as_bytes()),
+            (ColumnBuilder::Utf8(b), None) => b.push_null(),
+            (_, value) => panic!("aggregate result {:?} does not match output column type", value),
         }
     }
 
+    fn finish(self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Boolean(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Int8(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Int16(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Int32(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Int64(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::UInt8(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::UInt16(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::UInt32(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::UInt64(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Float32(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Float64(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Utf8(mut b) => Arc::new(b.finish()),
+        }
+    }
 }
 
 impl Relation for AggregateRelation {
@@ -292,24 +760,19 @@ impl AggregateRelation {
     fn without_group_by(&mut self) -> Result<Option<RecordBatch>> {
 
         let aggr_expr_count = self.aggr_expr.len();
-        let mut accumulator_set = create_accumulators(&self.aggr_expr);
+        let mut accumulator_set = create_accumulators(&self.aggr_expr, &self.context)?;
 
         while let Some(batch) = self.input.borrow_mut().next()? {
 
+            // feed each aggregate function the whole incoming array at once: every function now
+            // implements accumulate_array, so there's no per-row match+downcast here at all
             for i in 0..aggr_expr_count {
                 match &self.aggr_expr[i] {
-                    RuntimeExpr::AggregateFunction { f, args, t, .. } => {
-
-                        // evaluate argument to aggregate function
+                    RuntimeExpr::AggregateFunction { args, .. } => {
                         match args[0](&batch) {
-                            Ok(array) => match f {
-                                AggregateType::Min => accumulator_set.accumulate_scalar(i,array_min(array, &t)?),
-                                AggregateType::Max => accumulator_set.accumulate_scalar(i,array_max(array, &t)?),
-                                _ => return Err(ExecutionError::NotImplemented("Unsupported aggregate function".to_string()))
-                            }
+                            Ok(array) => accumulator_set.aggr_values[i].borrow_mut().accumulate_array(array),
                             Err(e) => return Err(ExecutionError::ExecutionError("Failed to evaluate argument to aggregate function".to_string()))
                         }
-
                     },
                     _ => return Err(ExecutionError::General("Invalid aggregate expression".to_string()))
                 }
@@ -319,23 +782,10 @@ impl AggregateRelation {
         let mut result_columns: Vec<ArrayRef> = vec![];
 
         for i in 0..aggr_expr_count {
-            let mut accum = accumulator_set.aggr_values[i].borrow();
-            match accum.data_type() {
-                DataType::Int32 => {
-                    let b = Int32Builder::new(1);
-                    result_columns.push(Arc::new(b.finish()));
-                }
-                DataType::Float64 => {
-                    let mut b = Float64Builder::new(1);
-                    match accum.result() {
-                        Some(ScalarValue::Float64(n)) => b.push(*n)?,
-                        Some(_) => panic!(),
-                        None => b.push_null()?
-                    };
-                    result_columns.push(Arc::new(b.finish()));
-                }
-                _ => unimplemented!()
-            }
+            let accum = accumulator_set.aggr_values[i].borrow();
+            let mut builder = ColumnBuilder::new(accum.data_type(), 1);
+            builder.push_aggregate_result(accum.result())?;
+            result_columns.push(builder.finish());
         }
 
         Ok(Some(RecordBatch::new(
@@ -350,6 +800,10 @@ impl AggregateRelation {
         let mut map: FnvHashMap<Vec<GroupByScalar>, Rc<RefCell<AccumulatorSet>>> =
             FnvHashMap::default();
 
+        // data types of the group-by columns, captured from the first batch so we know which
+        // concrete builder to use when assembling the output below
+        let mut group_by_types: Vec<DataType> = vec![];
+
         while let Some(batch) = self.input.borrow_mut().next()? {
 
             // evaulate the group by expressions on this batch
@@ -358,14 +812,13 @@ impl AggregateRelation {
                     .map(|e| e.get_func()(&batch))
                     .collect::<Result<Vec<ArrayRef>>>()?;
 
+            if group_by_types.is_empty() {
+                group_by_types = group_by_keys.iter().map(|k| k.data_type().clone()).collect();
+            }
 
-            // iterate over each row in the batch
-            for row in 0..batch.num_rows() {
-
-                //NOTE: this seems pretty inefficient, performing a match and a downcast on each row
-
-                // create key
-                let key: Vec<GroupByScalar> = group_by_keys.iter().map(|col| {
+            // compute the key for every row once per batch ...
+            let row_keys: Vec<Vec<GroupByScalar>> = (0..batch.num_rows()).map(|row| {
+                group_by_keys.iter().map(|col| {
                     //TODO: use macro to make this less verbose
                     match col.data_type() {
                         DataType::Int32 => {
@@ -379,42 +832,83 @@ impl AggregateRelation {
                         //TODO add all types
                         _ => unimplemented!()
                     }
-                }).collect();
+                }).collect()
+            }).collect();
+
+            // ... then partition the batch's row indices by that key, rather than performing a
+            // match+downcast to extract one scalar value per row per aggregate expression
+            let mut partitions: FnvHashMap<Vec<GroupByScalar>, Vec<usize>> = FnvHashMap::default();
+            for (row, key) in row_keys.into_iter().enumerate() {
+                partitions.entry(key).or_insert_with(Vec::new).push(row);
+            }
 
-                //TODO: find more elegant way to write this instead of hacking around ownership issues
+            // evaluate each aggregate expression's argument over the whole batch exactly once
+            let aggr_arrays: Vec<ArrayRef> = self.aggr_expr.iter().map(|e| match e {
+                RuntimeExpr::AggregateFunction { args, .. } => args[0](&batch),
+                _ => panic!("invalid aggregate expression"),
+            }).collect::<Result<Vec<ArrayRef>>>()?;
+
+            let aggr_types: Vec<DataType> = self.aggr_expr.iter().map(|e| match e {
+                RuntimeExpr::AggregateFunction { t, .. } => t.clone(),
+                _ => panic!("invalid aggregate expression"),
+            }).collect();
+
+            for (key, indices) in partitions.into_iter() {
+                if !map.contains_key(&key) {
+                    let set = create_accumulators(&self.aggr_expr, &self.context)?;
+                    map.insert(key.clone(), Rc::new(RefCell::new(set)));
+                }
+                let accumulator_set = map.get(&key).unwrap().clone();
+                let accumulator_set = accumulator_set.borrow();
+                for i in 0..aggr_arrays.len() {
+                    let sub_array = take(&aggr_arrays[i], &indices, &aggr_types[i]);
+                    accumulator_set.aggr_values[i].borrow_mut().accumulate_array(sub_array);
+                }
+            }
+        }
 
-                let updated = match map.get(&key) {
-                    Some(entry) => {
-                        let mut accumulator_set = entry.borrow_mut();
-                        update_accumulators(&batch, row, &mut accumulator_set, &self.aggr_expr);
-                        true
-                    }
-                    None => false
-                };
-
-                if !updated {
-                    let accumulator_set = Rc::new(RefCell::new(create_accumulators(&self.aggr_expr)));
-                    {
-                        let mut entry_mut = accumulator_set.borrow_mut();
-                        update_accumulators(&batch, row, &mut entry_mut, &self.aggr_expr);
+        // pre-allocate one builder per group-by column and one per aggregate expression, then
+        // walk the map exactly once, appending every column's value for each row as we go
+        let num_rows = map.len();
+        let mut group_builders: Vec<ColumnBuilder> = group_by_types.iter()
+            .map(|dt| ColumnBuilder::new(dt, num_rows))
+            .collect();
+        // each built-in's Signature already knows its true output type, e.g. SUM widens its
+        // input type, so that's resolved directly rather than building a throwaway accumulator
+        // just to read it back; AggregateType::Custom has no declared Signature, so a single
+        // accumulator is constructed as a fallback probe only when one is actually needed
+        let mut probe_accumulators: Option<AccumulatorSet> = None;
+        let mut aggr_builders: Vec<ColumnBuilder> = Vec::with_capacity(self.aggr_expr.len());
+        for (i, e) in self.aggr_expr.iter().enumerate() {
+            let dt = match e {
+                RuntimeExpr::AggregateFunction { f, t, .. } => match aggregate_output_type(f, t)? {
+                    Some(dt) => dt,
+                    None => {
+                        if probe_accumulators.is_none() {
+                            probe_accumulators = Some(create_accumulators(&self.aggr_expr, &self.context)?);
+                        }
+                        probe_accumulators.as_ref().unwrap().aggr_values[i].borrow().data_type().clone()
                     }
-                    map.insert(key.clone(), accumulator_set);
-                }
+                },
+                _ => return Err(ExecutionError::General("Invalid aggregate expression".to_string())),
+            };
+            aggr_builders.push(ColumnBuilder::new(&dt, num_rows));
+        }
+
+        for (key, accumulator_set) in map.iter() {
+            for (builder, value) in group_builders.iter_mut().zip(key.iter()) {
+                builder.push_group_value(value)?;
+            }
+            let accumulator_set = accumulator_set.borrow();
+            for (builder, accum) in aggr_builders.iter_mut().zip(accumulator_set.aggr_values.iter()) {
+                builder.push_aggregate_result(accum.borrow().result())?;
             }
         }
 
-        // create record batch from the accumulators
         let mut result_columns: Vec<ArrayRef> =
             Vec::with_capacity(self.group_expr.len() + self.aggr_expr.len());
-
-//        for i in 0..group_by_keys.len() {
-//            result_columns.push(group_by_keys[i].clone());
-//        }
-
-        //TODO build record batch from aggregate results
-        for (k, v) in map.iter() {
-
-        }
+        result_columns.extend(group_builders.into_iter().map(|b| b.finish()));
+        result_columns.extend(aggr_builders.into_iter().map(|b| b.finish()));
 
         Ok(Some(RecordBatch::new(
             self.schema.clone(),
@@ -454,7 +948,7 @@ mod tests {
             Field::new("min_lat", DataType::Float64, false),
         ]));
 
-        let mut projection = AggregateRelation::new(aggr_schema,relation, vec![], aggr_expr);
+        let mut projection = AggregateRelation::new(aggr_schema, relation, vec![], aggr_expr, Rc::new(context));
         let batch = projection.next().unwrap().unwrap();
         assert_eq!(1, batch.num_columns());
         let min_lat = batch.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
@@ -478,7 +972,7 @@ mod tests {
             Field::new("max_lat", DataType::Float64, false),
         ]));
 
-        let mut projection = AggregateRelation::new(aggr_schema,relation, vec![], aggr_expr);
+        let mut projection = AggregateRelation::new(aggr_schema, relation, vec![], aggr_expr, Rc::new(context));
         let batch = projection.next().unwrap().unwrap();
         assert_eq!(1, batch.num_columns());
         let max_lat = batch.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
@@ -503,4 +997,48 @@ mod tests {
         ))))
     }
 
+    /// Regression test: SUM over an Int32 column used to hit `array_sum`'s `_ => Err(...)` arm
+    /// and panic on the first batch, since only Float64 was implemented.
+    #[test]
+    fn sum_int32_multi_batch() {
+        SumFunction::signature().resolve(&DataType::Int32).unwrap();
+        let mut sum = SumFunction::new(&DataType::Int32);
+        sum.accumulate_array(Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef);
+        sum.accumulate_array(Arc::new(Int32Array::from(vec![4, 5])) as ArrayRef);
+        assert_eq!(Some(ScalarValue::Int64(15)), *sum.result());
+    }
+
+    #[test]
+    fn count_multi_batch() {
+        let mut count = CountFunction::new();
+        count.accumulate_array(Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef);
+        count.accumulate_array(Arc::new(Int32Array::from(vec![4, 5])) as ArrayRef);
+        assert_eq!(Some(ScalarValue::Int64(5)), *count.result());
+    }
+
+    /// Regression test: AVG's `accumulate_array` used to panic for any non-Float64 numeric type.
+    #[test]
+    fn avg_int32_multi_batch() {
+        let mut avg = AvgFunction::new(&DataType::Int32);
+        avg.accumulate_array(Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef);
+        avg.accumulate_array(Arc::new(Int32Array::from(vec![4, 5])) as ArrayRef);
+        assert_eq!(Some(ScalarValue::Float64(3.0)), *avg.result());
+    }
+
+    /// Regression test: merging a second Utf8 batch's MIN/MAX used to panic, since the scalar
+    /// merge `match` in `accumulate_scalar` had no `Utf8` arm (only the first-batch clone path
+    /// worked).
+    #[test]
+    fn min_max_utf8_multi_batch() {
+        let mut min = MinFunction::new(&DataType::Utf8);
+        min.accumulate_array(Arc::new(BinaryArray::from(vec!["banana", "apple"])) as ArrayRef);
+        min.accumulate_array(Arc::new(BinaryArray::from(vec!["cherry", "aardvark"])) as ArrayRef);
+        assert_eq!(Some(ScalarValue::Utf8("aardvark".to_string())), *min.result());
+
+        let mut max = MaxFunction::new(&DataType::Utf8);
+        max.accumulate_array(Arc::new(BinaryArray::from(vec!["banana", "apple"])) as ArrayRef);
+        max.accumulate_array(Arc::new(BinaryArray::from(vec!["cherry", "aardvark"])) as ArrayRef);
+        assert_eq!(Some(ScalarValue::Utf8("cherry".to_string())), *max.result());
+    }
+
 }
@@ -0,0 +1,45 @@
+// Copyright 2018 Grove Enterprises LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Execution-time state shared across a query's relations.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::aggregate::UdafFactory;
+
+/// Holds execution-time state for a query, currently just the registry of user-defined
+/// aggregate functions that `aggregate::create_accumulators` consults for any
+/// `AggregateType::Custom` expression it encounters.
+pub struct ExecutionContext {
+    udafs: RefCell<HashMap<String, UdafFactory>>,
+}
+
+impl ExecutionContext {
+    pub fn new() -> Self {
+        ExecutionContext { udafs: RefCell::new(HashMap::new()) }
+    }
+
+    /// Register a user-defined aggregate function under `name`, so a `SELECT my_udaf(col)` plan
+    /// can resolve it via `udaf` without this module knowing about it ahead of time. Registering
+    /// the same `name` twice replaces the previous factory.
+    pub fn register_udaf(&self, name: &str, factory: UdafFactory) {
+        self.udafs.borrow_mut().insert(name.to_string(), factory);
+    }
+
+    /// Look up a previously registered user-defined aggregate function by name.
+    pub fn udaf(&self, name: &str) -> Option<UdafFactory> {
+        self.udafs.borrow().get(name).cloned()
+    }
+}